@@ -4,202 +4,680 @@
 //  Slash star within a string literal does not start a comment
 //  A string literal starts with doublequote and ends at the first double quote
 //  A string literal does not end at a double quote preceded by a backslash
-//  
-#[derive(Debug, PartialEq)]
-pub enum Token {
+//
+/// A single source character, or a newline. Comment/string delimiters are no
+/// longer their own token kinds: which characters open a comment or string
+/// is a property of the `LanguageSpec` `parse` is driven by, not of the
+/// lexer.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TokenKind {
     Char(char),
-    Star,
     NewLine,
-    FrontSlash,
-    DoubleQuote,
+}
+
+/// A `TokenKind` plus where it started in the source: byte offset and
+/// 1-based line/column.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub pos: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// The byte range (and starting line/column) of source text that produced
+/// an `El`. For comments and string literals this covers the full text
+/// including delimiters, not just the decoded content.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Whether a doc comment documents the item that follows it (`Outer`, e.g.
+/// `/**`/`///`) or the item it's nested inside (`Inner`). `Inner` is written
+/// `//!`/`/*!` (the `!` replacing the doc marker's own decoration), matching
+/// real Rust; `///!`/`/**!` (`!` right after the full marker) are also
+/// accepted as `Inner`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AttrStyle {
+    Inner,
+    Outer,
+}
+
+/// A comment's relation to the code around it on its source line(s), so
+/// downstream formatting/doc-extraction tools know whether it's safe to
+/// reflow or relocate.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CommentPosition {
+    /// Only whitespace precedes it on its line, and nothing but a newline
+    /// follows it.
+    Isolated,
+    /// Code precedes it on its line, and nothing but a newline follows it.
+    Trailing,
+    /// A block comment with only whitespace before it on its line, but
+    /// code following it — e.g. `/* x */ code`.
+    Leading,
+    /// A block comment with code both before and after it on its line.
+    Mixed,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum El {
-    SingleLineComment(String),
-    BlockComment(String),
-    Code(String),
+    SingleLineComment(String, Span, CommentPosition),
+    BlockComment(String, Span, CommentPosition),
+    DocLineComment(String, Span, AttrStyle, CommentPosition),
+    DocBlockComment(String, Span, AttrStyle, CommentPosition),
+    Code(String, Span),
+    /// One or more consecutive blank (whitespace-only) lines, collapsed
+    /// into a single marker so layout can be reconstructed without
+    /// replaying every line individually.
+    BlankLine(Span),
+}
+
+/// Whether `built` (everything after the comment's opening `//`) is a doc
+/// comment, and if so which `AttrStyle`. `!` right after the opener (`//!`)
+/// is `Inner`; a third `/` (`///`) is `Outer`, unless immediately followed
+/// by a fourth (`////`, a plain decorative run) or by `!` (`///!`, also
+/// accepted as `Inner`). Anything else isn't a doc comment.
+fn line_attr_style(built: &str) -> Option<AttrStyle> {
+    let mut chars = built.chars();
+    match chars.next() {
+        Some('!') => Some(AttrStyle::Inner),
+        Some('/') => match chars.next() {
+            Some('/') => None,
+            Some('!') => Some(AttrStyle::Inner),
+            _ => Some(AttrStyle::Outer),
+        },
+        _ => None,
+    }
+}
+
+/// Like `line_attr_style`, for block comments: `built` is everything after
+/// the opening `/*`. `!` right after the opener (`/*!`) is `Inner`; a `*`
+/// (`/**`) is `Outer`, unless the body is nothing but more stars (`/**/`,
+/// `/***/`) or the opener is followed by yet another `*` (`/*** x */`,
+/// `/**** banner ****/`) — a decorative banner, not a doc comment (same
+/// rule as `////` for line comments) — or unless followed by `!` (`/**!`,
+/// also accepted as `Inner`).
+fn block_attr_style(built: &str) -> Option<AttrStyle> {
+    if built.chars().all(|c| c == '*') {
+        return None;
+    }
+    let mut chars = built.chars();
+    match chars.next() {
+        Some('!') => Some(AttrStyle::Inner),
+        Some('*') => match chars.next() {
+            Some('!') => Some(AttrStyle::Inner),
+            Some('*') => None,
+            _ => Some(AttrStyle::Outer),
+        },
+        _ => None,
+    }
 }
 pub enum State {
     SingleLineComment,
     BlockComment,
-    BlockCommentEnd,
     Comment,
     Code,
     StrLiteral,
+    /// Just past a `\` inside a string literal; the next token is appended
+    /// verbatim and can't end or restart the string.
+    StrLiteralEscape,
+    CharLiteral,
+    /// Just past a `\` inside a `char` literal; see `StrLiteralEscape`.
+    CharLiteralEscape,
 }
-fn lex(input: &str) -> Vec<Token> {
+pub fn tokenize(input: &str) -> Vec<Token> {
     let mut tokens = Vec::new();
+    let mut pos = 0;
+    let mut line = 1;
+    let mut col = 1;
     for c in input.chars() {
-        match c {
-            '*' => tokens.push(Token::Star),
-            '\n' => tokens.push(Token::NewLine),
-            '/' => tokens.push(Token::FrontSlash),
-            '"' => tokens.push(Token::DoubleQuote),
-            _ => tokens.push(Token::Char(c)),
+        let kind = if c == '\n' { TokenKind::NewLine } else { TokenKind::Char(c) };
+        tokens.push(Token { kind, pos, line, col });
+        pos += c.len_utf8();
+        if c == '\n' {
+            line += 1;
+            col = 1;
+        } else {
+            col += 1;
         }
     }
     tokens
 }
 
-fn parse(tokens: Vec<Token>) -> Vec<El> {
+/// A block comment's open/close delimiters, and whether it nests (a fresh
+/// `open` inside the comment starts a new level, requiring a matching
+/// number of `close`s to end it — needed for Rust's `/* /* */ */`).
+#[derive(Debug, Clone, Copy)]
+pub struct BlockCommentSpec {
+    pub open: &'static str,
+    pub close: &'static str,
+    pub nests: bool,
+}
+
+/// Describes one language's comment and string syntax, so the same FSM can
+/// lex/parse Java, Rust, JavaScript, Python, TOML, etc.
+#[derive(Debug, Clone, Copy)]
+pub struct LanguageSpec {
+    pub name: &'static str,
+    pub line_comment: Option<&'static str>,
+    pub block_comment: Option<BlockCommentSpec>,
+    pub quote: char,
+    /// Delimiter for a single `char`-like literal (e.g. Java/C `'a'`), if
+    /// the language has one. A quote char found inside a `char` literal
+    /// can't flip the machine into string mode, and vice versa.
+    pub char_quote: Option<char>,
+}
+
+impl LanguageSpec {
+    /// C-family syntax: `//`, non-nesting `/* */`, `"` strings. Covers
+    /// Java, C, C++ and JavaScript.
+    pub const C_LIKE: LanguageSpec = LanguageSpec {
+        name: "c-like",
+        line_comment: Some("//"),
+        block_comment: Some(BlockCommentSpec { open: "/*", close: "*/", nests: false }),
+        quote: '"',
+        char_quote: Some('\''),
+    };
+
+    /// Like `C_LIKE`, but `/* */` nests. `char_quote` is `None`: Rust's `'`
+    /// also opens lifetimes (`&'a str`), so a bare single-quote delimiter
+    /// can't tell a char literal from a lifetime tick apart. Treating `'`
+    /// as ordinary code means `'a'` char literals aren't specially
+    /// recognized, but that's strictly better than misparsing every
+    /// lifetime-bearing line as an unterminated char literal.
+    pub const RUST: LanguageSpec = LanguageSpec {
+        name: "rust",
+        line_comment: Some("//"),
+        block_comment: Some(BlockCommentSpec { open: "/*", close: "*/", nests: true }),
+        quote: '"',
+        char_quote: None,
+    };
+
+    /// `#` line comments, no block comments. Covers Python and TOML.
+    pub const HASH_COMMENTED: LanguageSpec = LanguageSpec {
+        name: "hash-commented",
+        line_comment: Some("#"),
+        block_comment: None,
+        quote: '"',
+        char_quote: None,
+    };
+}
+
+/// Picks a `LanguageSpec` from a file extension (without the leading dot),
+/// defaulting to `C_LIKE` for anything unrecognized.
+pub fn language_for_extension(ext: &str) -> LanguageSpec {
+    match ext {
+        "rs" => LanguageSpec::RUST,
+        "py" | "toml" => LanguageSpec::HASH_COMMENTED,
+        _ => LanguageSpec::C_LIKE,
+    }
+}
+
+/// Why `parse` rejected a token stream, carrying enough of the offending
+/// token's span to report a useful location.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// An unexpected token, with its span. `parse` itself never returns
+    /// this: an opener prefix that doesn't continue into a comment marker
+    /// (the `/` in `a / b`) is kept as ordinary code.
+    UnexpectedToken { found: TokenKind, span: Span },
+    /// The token stream ended with a block comment still open.
+    UnterminatedBlockComment { span: Span },
+    /// The token stream ended with a string literal still open.
+    UnterminatedString { span: Span },
+    /// The token stream ended with a `char` literal still open.
+    UnterminatedCharLiteral { span: Span },
+}
+
+/// Does `c` start a sequence that might open a comment under `spec`?
+fn opens_comment(spec: &LanguageSpec, c: char) -> bool {
+    spec.line_comment.is_some_and(|lc| lc.starts_with(c))
+        || spec.block_comment.is_some_and(|b| b.open.starts_with(c))
+}
+
+/// Does any non-whitespace character appear in `tokens` before the next
+/// newline (or before the token stream ends)? Used right after a block
+/// comment closes, to tell an `Isolated` comment apart from a `Leading`
+/// one, and a `Trailing` one apart from a `Mixed` one.
+fn code_follows_on_line(tokens: &[Token]) -> bool {
+    for t in tokens {
+        match t.kind {
+            TokenKind::NewLine => return false,
+            TokenKind::Char(c) if !c.is_whitespace() => return true,
+            TokenKind::Char(_) => {}
+        }
+    }
+    false
+}
+
+/// Result of comparing an in-progress opener `pending` against `spec`.
+enum CommentOpener {
+    /// `pending` is exactly a line-comment marker.
+    Line,
+    /// `pending` is exactly a block-comment opener.
+    Block,
+    /// `pending` is a strict prefix of some marker; keep accumulating.
+    Prefix,
+    /// `pending` can't lead to any marker in `spec`.
+    NoMatch,
+}
+
+fn classify_comment_opener(spec: &LanguageSpec, pending: &str) -> CommentOpener {
+    if Some(pending) == spec.line_comment {
+        CommentOpener::Line
+    } else if let Some(block) = spec.block_comment {
+        if pending == block.open {
+            CommentOpener::Block
+        } else if spec.line_comment.is_some_and(|lc| lc.starts_with(pending))
+            || block.open.starts_with(pending)
+        {
+            CommentOpener::Prefix
+        } else {
+            CommentOpener::NoMatch
+        }
+    } else if spec.line_comment.is_some_and(|lc| lc.starts_with(pending)) {
+        CommentOpener::Prefix
+    } else {
+        CommentOpener::NoMatch
+    }
+}
+
+pub fn parse(tokens: Vec<Token>, spec: &LanguageSpec) -> Result<Vec<El>, ParseError> {
     let mut state = State::Code;
     let mut built = String::new();
     let mut parsed :Vec<El>= vec![];
 
-    for t in tokens {
+    // Start (pos, line, col) of the `El` currently being built. `code_start`
+    // is only meaningful while `built` is non-empty; `delim_start` is set
+    // whenever we enter a comment or string literal, at its opening
+    // delimiter, so the emitted span covers the delimiters too.
+    let mut code_start: Option<(usize, usize, usize)> = None;
+    let mut delim_start: (usize, usize, usize) = (0, 1, 1);
+    // Byte offset just past the last token seen, so an unterminated
+    // construct at EOF can still report where it ends.
+    let mut end_pos = 0;
+    // While resolving a comment opener (`State::Comment`) or a block
+    // comment's close/nested-open delimiter (`State::BlockComment`), the
+    // candidate characters seen so far that haven't yet been classified.
+    let mut pending = String::new();
+    // How many levels of a nesting block comment are currently open.
+    let mut depth: usize = 0;
+    // Whether any non-whitespace `Code` character has been pushed since
+    // the last newline; decides a comment's `Isolated` vs `Trailing` side.
+    let mut line_has_code = false;
+    // `line_has_code` as of the comment currently open, captured at the
+    // moment it started.
+    let mut comment_had_code_before = false;
+    // Start of the current run of consecutive blank lines, if any.
+    let mut blank_run_start: Option<(usize, usize, usize)> = None;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let t = tokens[i];
+        let (pos, line, col) = (t.pos, t.line, t.col);
+        let c = match t.kind {
+            TokenKind::Char(c) => c,
+            TokenKind::NewLine => '\n',
+        };
+        end_pos = pos + c.len_utf8();
+        if !matches!(t.kind, TokenKind::NewLine) {
+            if let Some((bstart, bline, bcol)) = blank_run_start.take() {
+                parsed.push(El::BlankLine(Span { start: bstart, end: pos, line: bline, col: bcol }));
+            }
+        }
         state = match state {
         State::Code => {
-            match t {
-                Token::FrontSlash => {
-                    // push built to parsed
-                    if built.len() > 0 {
-                        parsed.push(El::Code(built.clone()));
-                        built.clear();
+            if c == spec.quote {
+                if !built.is_empty() {
+                    let (start, sline, scol) = code_start.unwrap();
+                    parsed.push(El::Code(built.clone(), Span { start, end: pos, line: sline, col: scol }));
+                    built.clear();
+                }
+                code_start = None;
+                delim_start = (pos, line, col);
+                line_has_code = true;
+                State::StrLiteral
+            } else if Some(c) == spec.char_quote {
+                if !built.is_empty() {
+                    let (start, sline, scol) = code_start.unwrap();
+                    parsed.push(El::Code(built.clone(), Span { start, end: pos, line: sline, col: scol }));
+                    built.clear();
+                }
+                code_start = None;
+                delim_start = (pos, line, col);
+                line_has_code = true;
+                State::CharLiteral
+            } else if matches!(t.kind, TokenKind::NewLine) {
+                if built.trim().is_empty() {
+                    // The line is blank only if `built` holds all of it (no
+                    // comment or literal was flushed from it earlier).
+                    if built.chars().count() + 1 == col && blank_run_start.is_none() {
+                        blank_run_start = Some(code_start.unwrap_or((pos, line, col)));
                     }
-                    State::Comment
-                },
-                Token::Char(c)=> {
-                    built.push(c);
-                    State::Code
+                    built.clear();
+                } else {
+                    let (start, sline, scol) = code_start.unwrap_or((pos, line, col));
+                    parsed.push(El::Code(built.clone(), Span { start, end: pos, line: sline, col: scol }));
+                    built.clear();
                 }
-                Token::Star => {
-                    built.push('*');
-                    State::Code
-                },
-                Token::NewLine => {
-                    parsed.push(El::Code(built.clone()));
+                code_start = None;
+                line_has_code = false;
+                State::Code
+            } else if opens_comment(spec, c) {
+                if !built.is_empty() {
+                    let (start, sline, scol) = code_start.unwrap();
+                    parsed.push(El::Code(built.clone(), Span { start, end: pos, line: sline, col: scol }));
                     built.clear();
-                    State::Code
-                },
-                Token::DoubleQuote => {
-                    if built.len() > 0 {
-                        parsed.push(El::Code(built.clone()));
-                        built.clear();
+                }
+                code_start = None;
+                delim_start = (pos, line, col);
+                comment_had_code_before = line_has_code;
+                pending.clear();
+                pending.push(c);
+                match classify_comment_opener(spec, &pending) {
+                    CommentOpener::Line => {
+                        pending.clear();
+                        State::SingleLineComment
+                    }
+                    CommentOpener::Block => {
+                        pending.clear();
+                        depth = 1;
+                        State::BlockComment
                     }
-                    State::StrLiteral
-                },
+                    CommentOpener::Prefix => State::Comment,
+                    CommentOpener::NoMatch => {
+                        // `opens_comment` guarantees `c` starts some marker,
+                        // so a lone character can never fail to classify.
+                        unreachable!("opens_comment guaranteed a prefix match")
+                    }
+                }
+            } else {
+                if built.is_empty() {
+                    code_start = Some((pos, line, col));
+                }
+                if !c.is_whitespace() {
+                    line_has_code = true;
+                }
+                built.push(c);
+                State::Code
             }
         }
         State::Comment => {
-            match t {
-                Token::FrontSlash =>
-                    State::SingleLineComment,
-                Token::Star => 
+            pending.push(c);
+            match classify_comment_opener(spec, &pending) {
+                CommentOpener::Line => {
+                    pending.clear();
+                    State::SingleLineComment
+                }
+                CommentOpener::Block => {
+                    pending.clear();
+                    depth = 1;
                     State::BlockComment
-                ,
-                t => {
-                    panic!("Unexpected token: {:?}", t);
+                }
+                CommentOpener::Prefix => State::Comment,
+                CommentOpener::NoMatch => {
+                    // `pending` never completed a marker — it was just
+                    // ordinary code that happened to start like one (e.g.
+                    // the bare `/` in `a / b`). Every `LanguageSpec` here
+                    // has markers at most two characters long, so `pending`
+                    // is exactly [dead char, current char]: flush the dead
+                    // one as code, then rewind `i` so the current token is
+                    // reprocessed fresh under `State::Code` — it might be a
+                    // newline, a quote, or just another character.
+                    let dead_len = pending.chars().count() - 1;
+                    let dead: String = pending.chars().take(dead_len).collect();
+                    if !dead.is_empty() {
+                        let (dstart, dline, dcol) = delim_start;
+                        if built.is_empty() {
+                            code_start = Some((dstart, dline, dcol));
+                        }
+                        if dead.chars().any(|ch| !ch.is_whitespace()) {
+                            line_has_code = true;
+                        }
+                        built.push_str(&dead);
+                    }
+                    pending.clear();
+                    i -= 1;
+                    State::Code
                 }
             }
         },
         State::SingleLineComment => {
-            match t {
-                Token::NewLine => {
-                    parsed.push(El::SingleLineComment(built.clone()));
-                    built.clear();
-                    State::Code
-                },
-                Token::Char(c) => {
-                    built.push(c);
-                    State::SingleLineComment
-                },
-                Token::Star => {
-                    built.push('*');
-                    State::SingleLineComment
-                },
-                Token::FrontSlash => {
-                    built.push('/');
-                    State::SingleLineComment
-                },
-                Token::DoubleQuote => {
-                    built.push('"');
-                    State::SingleLineComment
-                },
+            if matches!(t.kind, TokenKind::NewLine) {
+                let (start, sline, scol) = delim_start;
+                let span = Span { start, end: pos, line: sline, col: scol };
+                let position = if comment_had_code_before { CommentPosition::Trailing } else { CommentPosition::Isolated };
+                match line_attr_style(&built) {
+                    Some(style) => parsed.push(El::DocLineComment(built.clone(), span, style, position)),
+                    None => parsed.push(El::SingleLineComment(built.clone(), span, position)),
+                }
+                built.clear();
+                line_has_code = false;
+                State::Code
+            } else {
+                built.push(c);
+                State::SingleLineComment
             }
         },
         State::BlockComment => {
-            match t {
-                Token::Star => {
-                    State::BlockCommentEnd
-                },
-                Token::Char(c) => {
-                    built.push(c);
-                    State::BlockComment
-                },
-                Token::FrontSlash => {
-                    built.push('/');
-                    State::BlockComment
-                },
-                Token::DoubleQuote => {
-                    State::BlockComment
-                },
-                Token::NewLine => {
-                    built.push('\n');
-                    State::BlockComment
-                }
+            let block = spec.block_comment.expect("State::BlockComment requires a block_comment spec");
+            if matches!(t.kind, TokenKind::NewLine) {
+                line_has_code = false;
             }
-        }
-        State::StrLiteral => {
-            // TODO: collect the string literal
-            // TODO: Handle escaped chars
-            match t {
-                Token::DoubleQuote => {
-                    parsed.push(El::Code(built.clone()));
+            pending.push(c);
+            if pending == block.close {
+                if block.nests && depth > 1 {
+                    depth -= 1;
+                    built.push_str(&pending);
+                    pending.clear();
+                    State::BlockComment
+                } else {
+                    let (start, sline, scol) = delim_start;
+                    let span = Span { start, end: end_pos, line: sline, col: scol };
+                    let had_code_after = code_follows_on_line(&tokens[i + 1..]);
+                    let position = match (comment_had_code_before, had_code_after) {
+                        (false, false) => CommentPosition::Isolated,
+                        (true, false) => CommentPosition::Trailing,
+                        (false, true) => CommentPosition::Leading,
+                        (true, true) => CommentPosition::Mixed,
+                    };
+                    match block_attr_style(&built) {
+                        Some(style) => parsed.push(El::DocBlockComment(built.clone(), span, style, position)),
+                        None => parsed.push(El::BlockComment(built.clone(), span, position)),
+                    }
                     built.clear();
+                    pending.clear();
                     State::Code
                 }
-                Token::FrontSlash => {
-                    built.push('/');
-                    State::StrLiteral
-                }
-                Token::Star => {
-                    built.push('*');
-                    State::StrLiteral
-                }
-                Token::NewLine => {
-                    built.push('\n');
-                    State::StrLiteral
-                }
-                Token::Char(c) => {
-                    built.push(c);
-                    State::StrLiteral
+            } else if block.nests && pending == block.open {
+                depth += 1;
+                built.push_str(&pending);
+                pending.clear();
+                State::BlockComment
+            } else if block.close.starts_with(&pending)
+                || (block.nests && block.open.starts_with(&pending))
+            {
+                State::BlockComment
+            } else {
+                // `pending` as a whole can't extend into `close` (or a
+                // nested `open`), but a trailing suffix of it still might
+                // — e.g. the second `*` in `**/` is both the tail of a
+                // decorative run of stars and the head of the next `*/`.
+                // Flush only the prefix that can't participate in a future
+                // match; keep the longest viable suffix in `pending`.
+                let mut flush_upto = pending.len();
+                for (idx, _) in pending.char_indices().skip(1) {
+                    let suffix = &pending[idx..];
+                    if block.close.starts_with(suffix)
+                        || (block.nests && block.open.starts_with(suffix))
+                    {
+                        flush_upto = idx;
+                        break;
+                    }
                 }
+                built.push_str(&pending[..flush_upto]);
+                pending = pending[flush_upto..].to_string();
+                State::BlockComment
             }
         }
-        State::BlockCommentEnd => {
-            match t {
-                Token::FrontSlash => {
-                    parsed.push(El::BlockComment(built.clone()));
-                    built.clear();
-                    State::Code
-                },
-                Token::Star => {
-                    built.push('*');
-                    built.push('*');
-                    State::BlockComment
-                },
-                Token::DoubleQuote => {
-                    built.push('*');
-                    built.push('"');
-                    State::BlockComment
-                },
-                Token::Char(c) => {
-                    built.push('*');
-                    built.push(c);
-                    State::BlockComment
-                },
-                Token::NewLine => {
-                    built.push('*');
-                    built.push('\n');
-                    State::BlockComment
-                },
-                }
+        State::StrLiteral => {
+            if c == '\\' {
+                built.push(c);
+                State::StrLiteralEscape
+            } else if c == spec.quote {
+                let (start, sline, scol) = delim_start;
+                parsed.push(El::Code(built.clone(), Span { start, end: end_pos, line: sline, col: scol }));
+                built.clear();
+                State::Code
+            } else {
+                built.push(c);
+                State::StrLiteral
+            }
+        }
+        State::StrLiteralEscape => {
+            // Whatever follows `\` is part of the string, verbatim — it
+            // can't end or restart the string, even if it's a quote.
+            built.push(c);
+            State::StrLiteral
+        }
+        State::CharLiteral => {
+            if c == '\\' {
+                built.push(c);
+                State::CharLiteralEscape
+            } else if Some(c) == spec.char_quote {
+                let (start, sline, scol) = delim_start;
+                parsed.push(El::Code(built.clone(), Span { start, end: end_pos, line: sline, col: scol }));
+                built.clear();
+                State::Code
+            } else {
+                built.push(c);
+                State::CharLiteral
+            }
+        }
+        State::CharLiteralEscape => {
+            built.push(c);
+            State::CharLiteral
+        }
+    };
+    i += 1;
+    }
+
+    // The token stream is EOF here. `Code` and `SingleLineComment` don't
+    // need a closing delimiter, so flush whatever was pending; a lone
+    // opener prefix (`Comment`, e.g. the `/` of `a /`) is flushed as code;
+    // anything else still open is a genuine syntax error.
+    match state {
+        State::Code => {
+            if !built.is_empty() {
+                let (start, sline, scol) = code_start.unwrap();
+                parsed.push(El::Code(built.clone(), Span { start, end: end_pos, line: sline, col: scol }));
+            } else if let Some((bstart, bline, bcol)) = blank_run_start {
+                parsed.push(El::BlankLine(Span { start: bstart, end: end_pos, line: bline, col: bcol }));
             }
-        } 
+        }
+        State::SingleLineComment => {
+            let (start, sline, scol) = delim_start;
+            let span = Span { start, end: end_pos, line: sline, col: scol };
+            let position = if comment_had_code_before { CommentPosition::Trailing } else { CommentPosition::Isolated };
+            match line_attr_style(&built) {
+                Some(style) => parsed.push(El::DocLineComment(built.clone(), span, style, position)),
+                None => parsed.push(El::SingleLineComment(built.clone(), span, position)),
+            }
+        }
+        State::Comment => {
+            let (start, sline, scol) = delim_start;
+            parsed.push(El::Code(pending.clone(), Span { start, end: end_pos, line: sline, col: scol }));
+        }
+        State::BlockComment => {
+            let (start, sline, scol) = delim_start;
+            return Err(ParseError::UnterminatedBlockComment {
+                span: Span { start, end: end_pos, line: sline, col: scol },
+            });
+        }
+        State::StrLiteral | State::StrLiteralEscape => {
+            let (start, sline, scol) = delim_start;
+            return Err(ParseError::UnterminatedString {
+                span: Span { start, end: end_pos, line: sline, col: scol },
+            });
+        }
+        State::CharLiteral | State::CharLiteralEscape => {
+            let (start, sline, scol) = delim_start;
+            return Err(ParseError::UnterminatedCharLiteral {
+                span: Span { start, end: end_pos, line: sline, col: scol },
+            });
+        }
     }
-    parsed
+
+    Ok(parsed)
 }
+
+/// Strip the conventional Javadoc decoration from a block comment's raw
+/// body (leading `*` gutters, blank edge lines) and return the cleaned
+/// content as separate lines. This is opt-in: `parse` keeps the raw text so
+/// callers that need the exact source can still get it.
+///
+/// Vertical trim drops leading/trailing blank lines and a leading line that
+/// is entirely `*` characters (a decorative `/***` banner). Horizontal trim
+/// then removes the longest left prefix, common to all non-blank lines, of
+/// optional whitespace followed by an optional `* ` gutter.
+pub fn normalize_block_comment(body: &str) -> Vec<String> {
+    let mut lines: Vec<&str> = body.split('\n').collect();
+
+    while lines.first().is_some_and(|l| l.trim().is_empty()) {
+        lines.remove(0);
+    }
+    if lines.first().is_some_and(|l| is_all_stars(l)) {
+        lines.remove(0);
+    }
+    while lines.first().is_some_and(|l| l.trim().is_empty()) {
+        lines.remove(0);
+    }
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let prefix_len = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| gutter_prefix_len(l))
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|l| l[prefix_len.min(l.len())..].to_string())
+        .collect()
+}
+
+fn is_all_stars(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && trimmed.chars().all(|c| c == '*')
+}
+
+/// Length, in bytes, of `line`'s leading whitespace plus an optional `* `
+/// gutter right after it.
+fn gutter_prefix_len(line: &str) -> usize {
+    let ws_len: usize = line
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| c.len_utf8())
+        .sum();
+    let mut rest = line[ws_len..].chars();
+    match (rest.next(), rest.next()) {
+        (Some('*'), Some(' ')) => ws_len + 2,
+        _ => ws_len,
+    }
+}
+
 use std::env;
 
 fn main() {
@@ -212,101 +690,461 @@ fn main() {
     // read the file
     let contents = std::fs::read_to_string(filepath)
         .expect("Something went wrong reading the file");
-    let tokens = lex(&contents);
-    let parsed = parse(tokens);
-    println!("{:#?}", parsed);
+    let ext = std::path::Path::new(filepath)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    let spec = language_for_extension(ext);
+    let tokens = tokenize(&contents);
+    match parse(tokens, &spec) {
+        Ok(parsed) => println!("{:#?}", parsed),
+        Err(err) => eprintln!("failed to parse {}: {:?}", filepath, err),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{lex, parse, El};
+    use crate::{tokenize, parse, normalize_block_comment, El, ParseError, Span, AttrStyle, LanguageSpec, language_for_extension, CommentPosition};
 
     #[test]
     fn single_line_comment() {
         let input = "//single line comment\n";
-        let output = lex(input);
-        let parsed = parse(output);
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
         assert_eq!(parsed, vec![
-            El::SingleLineComment("single line comment".into())
+            El::SingleLineComment("single line comment".into(), Span { start: 0, end: 21, line: 1, col: 1 }, CommentPosition::Isolated)
         ]);
 
     }
     #[test]
     fn block_comment() {
         let input = "/*block comment*/";
-        let output = lex(input);
-        let parsed = parse(output);
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
         assert_eq!(parsed, vec![
-            El::BlockComment("block comment".into())
+            El::BlockComment("block comment".into(), Span { start: 0, end: 17, line: 1, col: 1 }, CommentPosition::Isolated)
         ]);
     }
 
     #[test]
     fn code() {
         let input = "let x = 1;\n";
-        let output = lex(input);
-        let parsed = parse(output);
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
         assert_eq!(parsed, vec![
-            El::Code("let x = 1;".into())
+            El::Code("let x = 1;".into(), Span { start: 0, end: 10, line: 1, col: 1 })
         ]);
     }
 
     #[test]
     fn multiline_comment() {
         let input = "/*\n*multi line block comment\n*/";
-        let output = lex(input);
-        let parsed = parse(output);
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
         assert_eq!(parsed, vec![
-            El::BlockComment("\n*multi line block comment\n".into())
+            El::BlockComment("\n*multi line block comment\n".into(), Span { start: 0, end: 31, line: 1, col: 1 }, CommentPosition::Isolated)
         ]);
     }
 
     #[test]
     fn single_line_comment_w_fake_block_comment() {
         let input = "//single line comment /* fake block comment */\n";
-        let output = lex(input);
-        let parsed = parse(output);
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
         assert_eq!(parsed, vec![
-            El::SingleLineComment("single line comment /* fake block comment */".into())
+            El::SingleLineComment("single line comment /* fake block comment */".into(), Span { start: 0, end: 46, line: 1, col: 1 }, CommentPosition::Isolated)
         ]);
     }
 
     #[test]
     fn block_comment_w_fake_single_line_comment() {
         let input = "/*block comment // fake single line comment */";
-        let output = lex(input);
-        let parsed = parse(output);
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
         assert_eq!(parsed, vec![
-            El::BlockComment("block comment // fake single line comment ".into())
+            El::BlockComment("block comment // fake single line comment ".into(), Span { start: 0, end: 46, line: 1, col: 1 }, CommentPosition::Isolated)
         ]);
     }
 
     #[test]
     fn string_w_slash() {
         let input = "\"/string w/ slash /\"";
-        let output = lex(input);
-        let parsed = parse(output);
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
         assert_eq!(parsed, vec![
-            El::Code("/string w/ slash /".into())
+            El::Code("/string w/ slash /".into(), Span { start: 0, end: 20, line: 1, col: 1 })
         ]);
     }
     #[test]
     fn string_w_star() {
         let input = "\"*string w/ star *\"";
-        let output = lex(input);
-        let parsed = parse(output);
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
         assert_eq!(parsed, vec![
-            El::Code("*string w/ star *".into())
+            El::Code("*string w/ star *".into(), Span { start: 0, end: 19, line: 1, col: 1 })
         ]);
     }
-    
+
     #[test]
     fn single_line_comment_from_slashes() {
         let input = "//////\n";
-        let output = lex(input);
-        let parsed = parse(output);
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::SingleLineComment("////".into(), Span { start: 0, end: 6, line: 1, col: 1 }, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn doc_block_comment() {
+        let input = "/** javadoc */";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
         assert_eq!(parsed, vec![
-            El::SingleLineComment("////".into())
+            El::DocBlockComment("* javadoc ".into(), Span { start: 0, end: 14, line: 1, col: 1 }, AttrStyle::Outer, CommentPosition::Isolated)
         ]);
     }
+
+    #[test]
+    fn doc_block_comment_inner() {
+        let input = "/**! inner */";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::DocBlockComment("*! inner ".into(), Span { start: 0, end: 13, line: 1, col: 1 }, AttrStyle::Inner, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn doc_block_comment_inner_rust_style() {
+        let input = "/*! inner */";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::DocBlockComment("! inner ".into(), Span { start: 0, end: 12, line: 1, col: 1 }, AttrStyle::Inner, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn doc_line_comment() {
+        let input = "///doc\n";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::DocLineComment("/doc".into(), Span { start: 0, end: 6, line: 1, col: 1 }, AttrStyle::Outer, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn doc_line_comment_inner() {
+        let input = "///!inner\n";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::DocLineComment("/!inner".into(), Span { start: 0, end: 9, line: 1, col: 1 }, AttrStyle::Inner, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn doc_line_comment_inner_rust_style() {
+        let input = "//!inner\n";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::DocLineComment("!inner".into(), Span { start: 0, end: 8, line: 1, col: 1 }, AttrStyle::Inner, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn empty_block_comment_is_not_doc() {
+        let input = "/**/";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::BlockComment("".into(), Span { start: 0, end: 4, line: 1, col: 1 }, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn normalize_strips_gutters_and_blank_edges() {
+        let body = "*\n * Line one.\n * Line two.\n ";
+        assert_eq!(normalize_block_comment(body), vec![
+            "Line one.".to_string(),
+            "Line two.".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn normalize_preserves_interior_blank_lines() {
+        let body = "*\n * Line one.\n\n * Line two.\n ";
+        assert_eq!(normalize_block_comment(body), vec![
+            "Line one.".to_string(),
+            "".to_string(),
+            "Line two.".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn normalize_empty_comment_is_empty() {
+        assert_eq!(normalize_block_comment(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn normalize_single_line_without_gutter_is_unchanged() {
+        assert_eq!(normalize_block_comment("block comment"), vec!["block comment".to_string()]);
+    }
+
+    #[test]
+    fn code_without_trailing_newline_is_flushed_at_eof() {
+        let input = "let x = 1;";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("let x = 1;".into(), Span { start: 0, end: 10, line: 1, col: 1 })
+        ]);
+    }
+
+    #[test]
+    fn single_line_comment_without_trailing_newline_is_flushed_at_eof() {
+        let input = "//comment";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::SingleLineComment("comment".into(), Span { start: 0, end: 9, line: 1, col: 1 }, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn unterminated_block_comment_is_an_error() {
+        let input = "/* abc";
+        let output = tokenize(input);
+        assert_eq!(parse(output, &LanguageSpec::C_LIKE), Err(ParseError::UnterminatedBlockComment {
+            span: Span { start: 0, end: 6, line: 1, col: 1 }
+        }));
+    }
+
+    #[test]
+    fn unterminated_string_is_an_error() {
+        let input = "\"abc";
+        let output = tokenize(input);
+        assert_eq!(parse(output, &LanguageSpec::C_LIKE), Err(ParseError::UnterminatedString {
+            span: Span { start: 0, end: 4, line: 1, col: 1 }
+        }));
+    }
+
+    #[test]
+    fn nested_block_comments_under_rust_spec() {
+        let input = "/* outer /* inner */ still open */";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::RUST).unwrap();
+        assert_eq!(parsed, vec![
+            El::BlockComment(" outer /* inner */ still open ".into(), Span { start: 0, end: 34, line: 1, col: 1 }, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn unclosed_nested_block_comment_is_unterminated_under_rust_spec() {
+        let input = "/* outer /* inner */ still open";
+        let output = tokenize(input);
+        assert_eq!(parse(output, &LanguageSpec::RUST), Err(ParseError::UnterminatedBlockComment {
+            span: Span { start: 0, end: 31, line: 1, col: 1 }
+        }));
+    }
+
+    #[test]
+    fn c_like_block_comments_do_not_nest() {
+        // Under C_LIKE, the first `*/` closes the comment; the rest of the
+        // line is ordinary code, unlike the RUST spec above.
+        let input = "/* outer /* inner */ still open";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::BlockComment(" outer /* inner ".into(), Span { start: 0, end: 20, line: 1, col: 1 }, CommentPosition::Leading),
+            El::Code(" still open".into(), Span { start: 20, end: 31, line: 1, col: 21 }),
+        ]);
+    }
+
+    #[test]
+    fn rust_lifetime_tick_is_not_a_char_literal() {
+        let input = "let x: &'a str = y; /* important */";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::RUST).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("let x: &'a str = y; ".into(), Span { start: 0, end: 20, line: 1, col: 1 }),
+            El::BlockComment(" important ".into(), Span { start: 20, end: 35, line: 1, col: 21 }, CommentPosition::Trailing),
+        ]);
+    }
+
+    #[test]
+    fn block_comment_with_overlapping_star_run_before_close() {
+        // All-star bodies (however many stars) are a decorative banner, not
+        // a doc comment — same as `/**/`.
+        let input = "/***/";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::BlockComment("*".into(), Span { start: 0, end: 5, line: 1, col: 1 }, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn decorative_star_banner_with_text_is_not_a_doc_comment() {
+        let input = "/**** banner ****/";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::BlockComment("*** banner ***".into(), Span { start: 0, end: 18, line: 1, col: 1 }, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn javadoc_banner_closing_with_double_star() {
+        let input = "/**\n * doc\n **/";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::DocBlockComment("*\n * doc\n *".into(), Span { start: 0, end: 15, line: 1, col: 1 }, AttrStyle::Outer, CommentPosition::Isolated)
+        ]);
+    }
+
+    #[test]
+    fn bare_slash_is_division_not_a_comment() {
+        let input = "a / b";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("a ".into(), Span { start: 0, end: 2, line: 1, col: 1 }),
+            El::Code("/ b".into(), Span { start: 2, end: 5, line: 1, col: 3 }),
+        ]);
+    }
+
+    #[test]
+    fn bare_slash_before_newline_is_not_a_comment() {
+        let input = "a /\n";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("a ".into(), Span { start: 0, end: 2, line: 1, col: 1 }),
+            El::Code("/".into(), Span { start: 2, end: 3, line: 1, col: 3 }),
+        ]);
+    }
+
+    #[test]
+    fn hash_commented_line_comment() {
+        let input = "# a python comment\nx = 1\n";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::HASH_COMMENTED).unwrap();
+        assert_eq!(parsed, vec![
+            El::SingleLineComment(" a python comment".into(), Span { start: 0, end: 18, line: 1, col: 1 }, CommentPosition::Isolated),
+            El::Code("x = 1".into(), Span { start: 19, end: 24, line: 2, col: 1 }),
+        ]);
+    }
+
+    #[test]
+    fn string_with_escaped_quote_does_not_end_early() {
+        let input = "\"a\\\"b\"";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("a\\\"b".into(), Span { start: 0, end: 6, line: 1, col: 1 })
+        ]);
+    }
+
+    #[test]
+    fn string_ending_in_escaped_backslash_closes_on_the_real_quote() {
+        let input = "\"end\\\\\"";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("end\\\\".into(), Span { start: 0, end: 7, line: 1, col: 1 })
+        ]);
+    }
+
+    #[test]
+    fn char_literal_with_escaped_quote() {
+        let input = "'\\''";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("\\'".into(), Span { start: 0, end: 4, line: 1, col: 1 })
+        ]);
+    }
+
+    #[test]
+    fn char_literal_containing_a_double_quote() {
+        let input = "'\"'";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("\"".into(), Span { start: 0, end: 3, line: 1, col: 1 })
+        ]);
+    }
+
+    #[test]
+    fn trailing_single_line_comment() {
+        let input = "let x = 1; // trailing\n";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("let x = 1; ".into(), Span { start: 0, end: 11, line: 1, col: 1 }),
+            El::SingleLineComment(" trailing".into(), Span { start: 11, end: 22, line: 1, col: 12 }, CommentPosition::Trailing),
+        ]);
+    }
+
+    #[test]
+    fn mixed_block_comment_has_code_before_and_after() {
+        let input = "let x = 1; /* mixed */ let y = 2;\n";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("let x = 1; ".into(), Span { start: 0, end: 11, line: 1, col: 1 }),
+            El::BlockComment(" mixed ".into(), Span { start: 11, end: 22, line: 1, col: 12 }, CommentPosition::Mixed),
+            El::Code(" let y = 2;".into(), Span { start: 22, end: 33, line: 1, col: 23 }),
+        ]);
+    }
+
+    #[test]
+    fn leading_block_comment_has_only_code_after() {
+        let input = "    /* x */ let y = 2;\n";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("    ".into(), Span { start: 0, end: 4, line: 1, col: 1 }),
+            El::BlockComment(" x ".into(), Span { start: 4, end: 11, line: 1, col: 5 }, CommentPosition::Leading),
+            El::Code(" let y = 2;".into(), Span { start: 11, end: 22, line: 1, col: 12 }),
+        ]);
+    }
+
+    #[test]
+    fn consecutive_blank_lines_collapse_to_one_marker() {
+        let input = "a\n\n\nb\n";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("a".into(), Span { start: 0, end: 1, line: 1, col: 1 }),
+            El::BlankLine(Span { start: 2, end: 4, line: 2, col: 1 }),
+            El::Code("b".into(), Span { start: 4, end: 5, line: 4, col: 1 }),
+        ]);
+    }
+
+    #[test]
+    fn whitespace_only_line_counts_as_blank() {
+        let input = "a\n   \nb\n";
+        let output = tokenize(input);
+        let parsed = parse(output, &LanguageSpec::C_LIKE).unwrap();
+        assert_eq!(parsed, vec![
+            El::Code("a".into(), Span { start: 0, end: 1, line: 1, col: 1 }),
+            El::BlankLine(Span { start: 2, end: 6, line: 2, col: 1 }),
+            El::Code("b".into(), Span { start: 6, end: 7, line: 3, col: 1 }),
+        ]);
+    }
+
+    #[test]
+    fn language_for_extension_maps_known_extensions() {
+        assert_eq!(language_for_extension("rs").name, "rust");
+        assert_eq!(language_for_extension("py").name, "hash-commented");
+        assert_eq!(language_for_extension("toml").name, "hash-commented");
+        assert_eq!(language_for_extension("java").name, "c-like");
+        assert_eq!(language_for_extension("unknown").name, "c-like");
+    }
 }